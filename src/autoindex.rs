@@ -0,0 +1,51 @@
+//! Auto-generated HTML directory listings, served when a directory has no
+//! `index.html` and the server has `autoindex` enabled.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Renders an HTML listing of `dir`'s entries, each linking to the child
+/// path under `request_path` (the URL path the listing was requested at).
+/// Entry names are HTML-escaped and sub-directories get a trailing `/`.
+pub(crate) fn render(dir: &Path, request_path: &str) -> io::Result<String> {
+    let mut entries: Vec<_> = fs::read_dir(dir)?.filter_map(|entry| entry.ok()).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut rows = String::new();
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let is_dir = entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false);
+        let suffix = if is_dir { "/" } else { "" };
+        let escaped = escape_html(&name);
+        rows.push_str(&format!(
+            "<li><a href=\"{}{}{}\">{}{}</a></li>\n",
+            request_path, escaped, suffix, escaped, suffix
+        ));
+    }
+
+    Ok(format!(
+        "<!DOCTYPE html>\n<html>\n<head><title>Index of {}</title></head>\n<body>\n<h1>Index of {}</h1>\n<ul>\n{}</ul>\n</body>\n</html>\n",
+        request_path, request_path, rows
+    ))
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_special_characters() {
+        assert_eq!(
+            escape_html("<script>&\"</script>"),
+            "&lt;script&gt;&amp;&quot;&lt;/script&gt;"
+        );
+    }
+}
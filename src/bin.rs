@@ -1,9 +1,5 @@
 use static_server::Server;
 
 fn main() {
-    Server::builder()
-        .allow_ext(&["png", "svg", "jpeg", "jpg"])
-        .prefix("/local")
-        .root("/")
-        .run();
+    Server::builder().prefix("/local").root("/").run();
 }
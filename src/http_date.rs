@@ -0,0 +1,113 @@
+//! Formatting and parsing of RFC 1123 dates, as used in `Last-Modified` and
+//! `If-Modified-Since` headers.
+//!
+//! There's no dependency on a date/time crate here - `SystemTime` only
+//! gives us seconds since the Unix epoch, so this converts that directly
+//! to and from the civil calendar using Howard Hinnant's `civil_from_days`
+//! algorithm.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Formats a `SystemTime` as an RFC 1123 date, e.g. `Thu, 01 Jan 1970 00:00:00 GMT`.
+pub(crate) fn format(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs();
+
+    let days = (secs / 86400) as i64;
+    let time_of_day = secs % 86400;
+    let (hour, minute, second) = (time_of_day / 3600, (time_of_day / 60) % 60, time_of_day % 60);
+    let (year, month, day) = civil_from_days(days);
+    let weekday = WEEKDAYS[(days.rem_euclid(7)) as usize];
+
+    format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        weekday,
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        hour,
+        minute,
+        second
+    )
+}
+
+/// Parses an RFC 1123 date back into a `SystemTime`, returning `None` if
+/// `value` isn't in the expected shape.
+pub(crate) fn parse(value: &str) -> Option<SystemTime> {
+    let parts: Vec<&str> = value.trim().split(' ').collect();
+    let [_weekday, day, month, year, time, _gmt] = parts[..] else {
+        return None;
+    };
+
+    let day: i64 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as i64 + 1;
+    let year: i64 = year.parse().ok()?;
+
+    let mut time_parts = time.split(':');
+    let hour: i64 = time_parts.next()?.parse().ok()?;
+    let minute: i64 = time_parts.next()?.parse().ok()?;
+    let second: i64 = time_parts.next()?.parse().ok()?;
+
+    let days = days_from_civil(year, month, day);
+    let secs = days * 86400 + hour * 3600 + minute * 60 + second;
+    if secs < 0 {
+        return None;
+    }
+
+    Some(UNIX_EPOCH + Duration::from_secs(secs as u64))
+}
+
+/// Days since the Unix epoch (1970-01-01) for a given civil date.
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Inverse of `days_from_civil`: turns a day count since the Unix epoch
+/// into a `(year, month, day)` civil date.
+fn civil_from_days(z: i64) -> (i64, i64, i64) {
+    let z = z + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let y = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    (if m <= 2 { y + 1 } else { y }, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_epoch() {
+        assert_eq!(format(UNIX_EPOCH), "Thu, 01 Jan 1970 00:00:00 GMT");
+    }
+
+    #[test]
+    fn round_trips_through_format_and_parse() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let formatted = format(time);
+        assert_eq!(parse(&formatted), Some(time));
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert_eq!(parse("not a date"), None);
+    }
+}
@@ -1,14 +1,46 @@
-use std::fs::File;
-use std::io::{self, BufRead, BufReader, Write};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
 use std::net::{TcpListener, TcpStream};
 use std::path::{Path, PathBuf};
-use std::thread;
+use std::time::SystemTime;
+
+mod autoindex;
+mod http_date;
+mod mime;
+mod range;
+mod thread_pool;
+use thread_pool::ThreadPool;
+
+/// Evaluates `If-None-Match`/`If-Modified-Since` against a file's current
+/// `etag`/`modified` time, per the precedence rule that `If-None-Match`,
+/// when present, wins over `If-Modified-Since`.
+fn is_not_modified(headers: &HashMap<String, String>, etag: &str, modified: SystemTime) -> bool {
+    if let Some(if_none_match) = headers.get("if-none-match") {
+        return if_none_match
+            .split(',')
+            .any(|candidate| candidate.trim() == etag || candidate.trim() == "*");
+    }
+
+    if let Some(if_modified_since) = headers.get("if-modified-since") {
+        if let Some(since) = http_date::parse(if_modified_since) {
+            // HTTP dates only carry whole seconds, so compare at that
+            // resolution (as `Last-Modified` was sent).
+            return http_date::parse(&http_date::format(modified))
+                .map_or(false, |modified| modified <= since);
+        }
+    }
+
+    false
+}
 
 #[derive(Clone)]
 pub struct Server {
     allowed_exts: Option<Vec<String>>,
     prefix: String,
     root: PathBuf,
+    workers: usize,
+    autoindex: bool,
 }
 
 impl Server {
@@ -20,12 +52,20 @@ impl Server {
         let listener = TcpListener::bind("127.0.0.1:8000").unwrap();
         println!("listening for connections at 8000");
 
+        let pool = ThreadPool::new(self.workers);
+
         for stream in listener.incoming() {
             let server = self.clone();
             match stream {
                 Ok(stream) => {
-                    thread::spawn(move || {
-                        server.handle_client(stream).unwrap();
+                    pool.execute(move || {
+                        // A panicking job would permanently take a worker
+                        // down, so report and move on instead of
+                        // `unwrap()`-ing an I/O error (e.g. a client that
+                        // disconnects mid-response).
+                        if let Err(e) = server.handle_client(stream) {
+                            println!("error handling client: {}", e);
+                        }
                     });
                 }
                 Err(e) => {
@@ -40,16 +80,136 @@ impl Server {
         let mut req = String::new();
         read_stream.read_line(&mut req).unwrap();
         let (method, path) = parse_request(req);
+        let request_path_str = path.to_string_lossy().into_owned();
+        let headers = read_headers(&mut read_stream);
 
         // validate request
-        assert_eq!(method, "GET");
-        let path = process_path(path, &self.allowed_exts, &self.prefix, &self.root).unwrap();
-
-        match File::open(path) {
-            Ok(file) => {
-                let mut buf_reader = BufReader::new(file);
-                stream.write_all(OK_HEADER.as_bytes())?;
-                io::copy(&mut buf_reader, &mut stream)?;
+        let head_only = match method.as_str() {
+            "GET" => false,
+            "HEAD" => true,
+            _ => {
+                stream.write_all(METHOD_NOT_ALLOWED_HEADER.as_bytes())?;
+                stream.flush().unwrap();
+                return Ok(());
+            }
+        };
+        let resolved = match process_path(path, &self.allowed_exts, &self.prefix, &self.root) {
+            Ok(resolved) => resolved,
+            Err(FORBIDDEN_ERR) => {
+                stream.write_all(FORBIDDEN_HEADER.as_bytes())?;
+                stream.flush().unwrap();
+                return Ok(());
+            }
+            Err(_) => {
+                stream.write_all(SERVER_ERR_HEADER.as_bytes())?;
+                stream.flush().unwrap();
+                return Ok(());
+            }
+        };
+
+        let path = if resolved.is_dir {
+            let index = resolved.path.join("index.html");
+            if index.is_file() {
+                index
+            } else if self.autoindex {
+                let request_path = if request_path_str.ends_with('/') {
+                    request_path_str.clone()
+                } else {
+                    format!("{}/", request_path_str)
+                };
+                let body = autoindex::render(&resolved.path, &request_path)?;
+                stream.write_all(
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\n\r\n",
+                        body.len()
+                    )
+                    .as_bytes(),
+                )?;
+                if !head_only {
+                    stream.write_all(body.as_bytes())?;
+                }
+                stream.flush().unwrap();
+                return Ok(());
+            } else {
+                stream.write_all(FORBIDDEN_HEADER.as_bytes())?;
+                stream.flush().unwrap();
+                return Ok(());
+            }
+        } else {
+            resolved.path
+        };
+
+        match File::open(&path) {
+            Ok(mut file) => {
+                let metadata = fs::metadata(&path)?;
+                let len = metadata.len();
+                let modified = metadata.modified()?;
+                let last_modified = http_date::format(modified);
+                let etag = format!(
+                    "W/\"{:x}-{:x}\"",
+                    modified
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs(),
+                    len
+                );
+                let content_type = path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .map(mime::guess)
+                    .unwrap_or("application/octet-stream");
+
+                if is_not_modified(&headers, &etag, modified) {
+                    stream.write_all(
+                        format!(
+                            "HTTP/1.1 304 Not Modified\r\nETag: {}\r\nLast-Modified: {}\r\n\r\n",
+                            etag, last_modified
+                        )
+                        .as_bytes(),
+                    )?;
+                    stream.flush().unwrap();
+                    return Ok(());
+                }
+
+                let range = headers.get("range").and_then(|h| range::parse(h));
+
+                match range.map(|r| r.resolve(len)) {
+                    Some(None) => {
+                        stream.write_all(
+                            format!(
+                                "HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */{}\r\n\r\n",
+                                len
+                            )
+                            .as_bytes(),
+                        )?;
+                    }
+                    Some(Some((start, end))) => {
+                        let body_len = end - start + 1;
+                        stream.write_all(
+                            format!(
+                                "HTTP/1.1 206 Partial Content\r\nAccept-Ranges: bytes\r\nContent-Type: {}\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\nLast-Modified: {}\r\nETag: {}\r\n\r\n",
+                                content_type, start, end, len, body_len, last_modified, etag
+                            )
+                            .as_bytes(),
+                        )?;
+                        if !head_only {
+                            file.seek(SeekFrom::Start(start))?;
+                            io::copy(&mut file.take(body_len), &mut stream)?;
+                        }
+                    }
+                    None => {
+                        stream.write_all(
+                            format!(
+                                "HTTP/1.1 200 OK\r\nAccept-Ranges: bytes\r\nContent-Type: {}\r\nContent-Length: {}\r\nLast-Modified: {}\r\nETag: {}\r\n\r\n",
+                                content_type, len, last_modified, etag
+                            )
+                            .as_bytes(),
+                        )?;
+                        if !head_only {
+                            io::copy(&mut BufReader::new(file), &mut stream)?;
+                        }
+                    }
+                }
             }
             Err(e) if e.kind() == io::ErrorKind::NotFound => {
                 stream.write_all(NOT_FOUND_HEADER.as_bytes())?;
@@ -65,11 +225,37 @@ impl Server {
     }
 }
 
+/// Reads the `Name: value` request headers following the request line,
+/// stopping at the blank line that terminates the header block.
+///
+/// Header names are lower-cased so lookups are case-insensitive.
+fn read_headers(read_stream: &mut BufReader<&TcpStream>) -> HashMap<String, String> {
+    let mut headers = HashMap::new();
+
+    loop {
+        let mut line = String::new();
+        if read_stream.read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    headers
+}
+
 #[derive(Default)]
 pub struct ServerBuilder {
     allowed_exts: Option<Vec<String>>,
     prefix: Option<String>,
     root: Option<PathBuf>,
+    workers: Option<usize>,
+    autoindex: bool,
 }
 
 impl ServerBuilder {
@@ -94,11 +280,31 @@ impl ServerBuilder {
         self
     }
 
+    /// Sets the number of worker threads that handle connections.
+    /// Defaults to the number of available CPUs.
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = Some(workers);
+        self
+    }
+
+    /// When a directory has no `index.html`, serve an auto-generated HTML
+    /// listing of its entries instead of a `403`. Off by default.
+    pub fn autoindex(mut self, autoindex: bool) -> Self {
+        self.autoindex = autoindex;
+        self
+    }
+
     pub fn build(self) -> Server {
         Server {
             allowed_exts: self.allowed_exts,
             prefix: self.prefix.expect("prefix is required"),
             root: self.root.expect("root is required"),
+            workers: self.workers.unwrap_or_else(|| {
+                std::thread::available_parallelism()
+                    .map(|n| n.get())
+                    .unwrap_or(4)
+            }),
+            autoindex: self.autoindex,
         }
     }
 
@@ -108,9 +314,10 @@ impl ServerBuilder {
     }
 }
 
-const OK_HEADER: &str = "HTTP/1.1 200 OK\r\n\r\n";
 const NOT_FOUND_HEADER: &str = "HTTP/1.1 404 ServerError\r\n\r\n";
 const SERVER_ERR_HEADER: &str = "HTTP/1.1 400 ServerError\r\n\r\n";
+const FORBIDDEN_HEADER: &str = "HTTP/1.1 403 Forbidden\r\n\r\n";
+const METHOD_NOT_ALLOWED_HEADER: &str = "HTTP/1.1 405 Method Not Allowed\r\nAllow: GET, HEAD\r\n\r\n";
 
 fn parse_request(req: String) -> (String, PathBuf) {
     let mut parts = req.split(' ');
@@ -122,13 +329,23 @@ fn parse_request(req: String) -> (String, PathBuf) {
 
 const PREFIX_ERR: &str = "prefix not found in url";
 const EXTENSION_MISMATCH_ERR: &str = "path extension doesn't match allowed values";
+const FORBIDDEN_ERR: &str = "path escapes server root";
+
+/// A request path resolved to somewhere under `root`.
+#[derive(Debug, PartialEq, Eq)]
+struct ResolvedPath {
+    path: PathBuf,
+    is_dir: bool,
+}
 
 fn process_path(
     path: PathBuf,
     allowed_exts: &Option<Vec<String>>,
     prefix: &str,
     root: &PathBuf,
-) -> Result<PathBuf, &'static str> {
+) -> Result<ResolvedPath, &'static str> {
+    let decoded = percent_decode(path.to_str().ok_or(FORBIDDEN_ERR)?);
+    let path = PathBuf::from(decoded);
     let path = path.strip_prefix(prefix).map_err(|_| PREFIX_ERR)?;
 
     if let Some(allowed_exts) = allowed_exts {
@@ -138,14 +355,107 @@ fn process_path(
             .to_str()
             .unwrap()
             .to_string();
-        dbg!(&extension);
         if !allowed_exts.contains(extension) {
             return Err(EXTENSION_MISMATCH_ERR);
         }
     };
 
-    // build final path
-    Ok(Path::new(root).join(path))
+    // build final path, then make sure it didn't climb out of root via `..`
+    let joined = Path::new(root).join(path);
+    let normalized = normalize_path(&joined);
+    if !normalized.starts_with(normalize_path(root)) {
+        return Err(FORBIDDEN_ERR);
+    }
+
+    // The lexical check above can't catch a symlink inside `root` that
+    // points outside of it, so canonicalize the longest existing ancestor
+    // (resolving any symlinks along the way) and check *that* against the
+    // canonical root. Components that don't exist yet can't be symlinks,
+    // so it's enough to check the deepest existing ancestor rather than
+    // the full path - that also keeps a simple "file not found" a 404
+    // instead of turning it into a 403.
+    if !within_canonical_root(&normalized, root) {
+        return Err(FORBIDDEN_ERR);
+    }
+
+    let is_dir = fs::metadata(&normalized)
+        .map(|m| m.is_dir())
+        .unwrap_or(false);
+
+    Ok(ResolvedPath {
+        path: normalized,
+        is_dir,
+    })
+}
+
+/// Decodes `%xx` percent-escapes in a URL path into their raw bytes.
+/// Invalid escapes (non-hex digits, a trailing `%`) are left as-is.
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hi = (bytes[i + 1] as char).to_digit(16);
+            let lo = (bytes[i + 2] as char).to_digit(16);
+            if let (Some(hi), Some(lo)) = (hi, lo) {
+                out.push((hi * 16 + lo) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Resolves symlinks via the deepest existing ancestor of `path` and
+/// checks that it still falls under `root` once canonicalized. Returns
+/// `false` (treated as forbidden) if `root` itself can't be canonicalized.
+fn within_canonical_root(path: &Path, root: &Path) -> bool {
+    let canonical_root = match root.canonicalize() {
+        Ok(root) => root,
+        Err(_) => return false,
+    };
+
+    let mut existing = path;
+    loop {
+        if existing.exists() {
+            break;
+        }
+        match existing.parent() {
+            Some(parent) => existing = parent,
+            None => break,
+        }
+    }
+
+    match existing.canonicalize() {
+        Ok(canonical) => canonical.starts_with(&canonical_root),
+        Err(_) => false,
+    }
+}
+
+/// Lexically resolves `.`/`..` components, without touching the filesystem
+/// (the target file may not exist yet, and a missing file should still be
+/// a 404, not a 403). A `..` that would climb above the path's own root
+/// component is simply dropped, which is what makes an escape attempt like
+/// `/root/../../etc/passwd` normalize to `/etc/passwd` - no longer
+/// prefixed by `root` once compared by the caller.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                out.pop();
+            }
+            std::path::Component::CurDir => {}
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
 }
 
 #[cfg(test)]
@@ -161,7 +471,13 @@ mod tests {
             &PathBuf::from("/root"),
         );
 
-        assert_eq!(path, Ok(PathBuf::from("/root/some/url.jpg")));
+        assert_eq!(
+            path,
+            Ok(ResolvedPath {
+                path: PathBuf::from("/root/some/url.jpg"),
+                is_dir: false,
+            })
+        );
     }
 
     #[test]
@@ -187,4 +503,83 @@ mod tests {
 
         assert_eq!(path, Err(PREFIX_ERR));
     }
+
+    #[test]
+    fn rejects_encoded_dot_segments() {
+        let path = process_path(
+            PathBuf::from("/prefix/%2e%2e/%2e%2e/etc/passwd"),
+            &None,
+            "/prefix",
+            &PathBuf::from("/root"),
+        );
+
+        assert_eq!(path, Err(FORBIDDEN_ERR));
+    }
+
+    #[test]
+    fn rejects_plain_dot_segments() {
+        let path = process_path(
+            PathBuf::from("/prefix/../../etc/passwd"),
+            &None,
+            "/prefix",
+            &PathBuf::from("/root"),
+        );
+
+        assert_eq!(path, Err(FORBIDDEN_ERR));
+    }
+
+    #[test]
+    fn rejects_embedded_absolute_escape() {
+        let path = process_path(
+            PathBuf::from("/prefix/%2f%2e%2e%2fetc%2fpasswd"),
+            &None,
+            "/prefix",
+            &PathBuf::from("/root"),
+        );
+
+        assert_eq!(path, Err(FORBIDDEN_ERR));
+    }
+
+    #[test]
+    fn allows_harmless_dot_segments() {
+        let path = process_path(
+            PathBuf::from("/prefix/./some/../url.jpg"),
+            &None,
+            "/prefix",
+            &PathBuf::from("/root"),
+        );
+
+        assert_eq!(
+            path,
+            Ok(ResolvedPath {
+                path: PathBuf::from("/root/url.jpg"),
+                is_dir: false,
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn rejects_symlink_escaping_root() {
+        use std::os::unix::fs::symlink;
+
+        let base = std::env::temp_dir().join(format!("static-server-rs-test-{}", std::process::id()));
+        let root = base.join("root");
+        let outside = base.join("outside");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("secret.txt"), b"secret").unwrap();
+        symlink(&outside, root.join("escape")).unwrap();
+
+        let path = process_path(
+            PathBuf::from("/prefix/escape/secret.txt"),
+            &None,
+            "/prefix",
+            &root,
+        );
+
+        assert_eq!(path, Err(FORBIDDEN_ERR));
+
+        fs::remove_dir_all(&base).unwrap();
+    }
 }
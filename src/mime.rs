@@ -0,0 +1,50 @@
+//! A small, static extension-to-MIME-type table.
+//!
+//! This covers the file types a static file server is commonly asked to
+//! serve. Anything not listed falls back to `application/octet-stream`,
+//! which prompts browsers to download rather than guess-render the file.
+
+/// Looks up the MIME type for a file extension (case-insensitive, without
+/// the leading dot).
+pub(crate) fn guess(extension: &str) -> &'static str {
+    match extension.to_lowercase().as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "txt" => "text/plain",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "webp" => "image/webp",
+        "wasm" => "application/wasm",
+        "pdf" => "application/pdf",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mp3" => "audio/mpeg",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_known_extensions() {
+        assert_eq!(guess("png"), "image/png");
+        assert_eq!(guess("SVG"), "image/svg+xml");
+        assert_eq!(guess("json"), "application/json");
+    }
+
+    #[test]
+    fn defaults_to_octet_stream() {
+        assert_eq!(guess("xyz"), "application/octet-stream");
+    }
+}
@@ -0,0 +1,118 @@
+//! Parsing and resolution of HTTP `Range` request headers.
+
+/// A `Range` header value, before it has been resolved against a concrete
+/// file length.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum RangeSpec {
+    /// `bytes=500-` - everything from `start` to the end of the file.
+    From(u64),
+    /// `bytes=0-1023` - an inclusive `[start, end]` byte range.
+    Full(u64, u64),
+    /// `bytes=-500` - the last `n` bytes of the file.
+    Suffix(u64),
+}
+
+/// Parses the value of a `Range: bytes=...` header.
+///
+/// Only the single-range forms are supported; anything else (missing
+/// `bytes=` prefix, multiple ranges, malformed numbers) returns `None` so
+/// the caller can fall back to a normal, full-body response.
+pub(crate) fn parse(header: &str) -> Option<RangeSpec> {
+    let spec = header.trim().strip_prefix("bytes=")?;
+    let (start, end) = spec.split_once('-')?;
+
+    if start.is_empty() {
+        let suffix_len = end.parse().ok()?;
+        return Some(RangeSpec::Suffix(suffix_len));
+    }
+
+    let start = start.parse().ok()?;
+    if end.is_empty() {
+        Some(RangeSpec::From(start))
+    } else {
+        Some(RangeSpec::Full(start, end.parse().ok()?))
+    }
+}
+
+impl RangeSpec {
+    /// Resolves this range against a file of `len` bytes, returning the
+    /// concrete inclusive `[start, end]` interval to serve.
+    ///
+    /// Returns `None` when the range is unsatisfiable, i.e. `start >= len`
+    /// or (for `Full`) the range is inverted, i.e. `start > end`.
+    pub(crate) fn resolve(&self, len: u64) -> Option<(u64, u64)> {
+        if len == 0 {
+            return None;
+        }
+
+        let (start, end) = match *self {
+            RangeSpec::From(start) => (start, len - 1),
+            RangeSpec::Full(start, end) => {
+                if start > end {
+                    return None;
+                }
+                (start, end.min(len - 1))
+            }
+            RangeSpec::Suffix(n) => {
+                let start = len.saturating_sub(n);
+                (start, len - 1)
+            }
+        };
+
+        if start >= len {
+            None
+        } else {
+            Some((start, end))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_from() {
+        assert_eq!(parse("bytes=500-"), Some(RangeSpec::From(500)));
+    }
+
+    #[test]
+    fn parses_full() {
+        assert_eq!(parse("bytes=0-1023"), Some(RangeSpec::Full(0, 1023)));
+    }
+
+    #[test]
+    fn parses_suffix() {
+        assert_eq!(parse("bytes=-500"), Some(RangeSpec::Suffix(500)));
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        assert_eq!(parse("items=0-1"), None);
+        assert_eq!(parse("bytes="), None);
+    }
+
+    #[test]
+    fn resolves_within_bounds() {
+        assert_eq!(RangeSpec::Full(0, 1023).resolve(2000), Some((0, 1023)));
+        assert_eq!(RangeSpec::From(500).resolve(1000), Some((500, 999)));
+        assert_eq!(RangeSpec::Suffix(500).resolve(1000), Some((500, 999)));
+    }
+
+    #[test]
+    fn clamps_end_to_len() {
+        assert_eq!(RangeSpec::Full(0, 9999).resolve(1000), Some((0, 999)));
+        assert_eq!(RangeSpec::Suffix(9999).resolve(1000), Some((0, 999)));
+    }
+
+    #[test]
+    fn rejects_start_past_len() {
+        assert_eq!(RangeSpec::From(1000).resolve(1000), None);
+        assert_eq!(RangeSpec::Full(1000, 1010).resolve(1000), None);
+    }
+
+    #[test]
+    fn rejects_inverted_range() {
+        assert_eq!(RangeSpec::Full(500, 100).resolve(1000), None);
+    }
+}